@@ -2,49 +2,51 @@
 #![no_main]
 #![feature(impl_trait_in_assoc_type)]
 
-use core::{future::Future, net::Ipv4Addr, sync::atomic::AtomicU16};
+use core::{future::Future, net::Ipv4Addr, sync::atomic::AtomicU32};
 
-use cyw43::JoinOptions;
-use cyw43_pio::PioSpi;
-use embassy_net::Ipv4Cidr;
-use embassy_rp::{
-    adc::Async, gpio::{Level, Output}, peripherals::{DMA_CH0, PIO0}, pio::Pio
-};
+use embassy_net::{tcp::TcpSocket, Ipv4Cidr};
+use embassy_rp::adc::Async;
 
 use defmt_rtt as _;
 use embassy_time::Duration;
+use embedded_io_async::{Read as _, Write as _};
 use panic_probe as _;
 use picoserve::{make_static, routing::get, AppBuilder, AppRouter};
-use rand::Rng;
 // use defmt::*;
 use core::fmt::Write;
 
+mod metrics;
+mod net;
+mod provisioning;
+
+use metrics::Metric;
+use net::LinkControl;
+
 embassy_rp::bind_interrupts!(struct Irqs {
     PIO0_IRQ_0 => embassy_rp::pio::InterruptHandler<embassy_rp::peripherals::PIO0>;
     ADC_IRQ_FIFO => embassy_rp::adc::InterruptHandler;
 });
 
-#[embassy_executor::task]
-async fn wifi_task(
-    runner: cyw43::Runner<'static, Output<'static>, PioSpi<'static, PIO0, 0, DMA_CH0>>,
-) -> ! {
-    runner.run().await
-}
-
-#[embassy_executor::task]
-async fn net_task(mut stack: embassy_net::Runner<'static, cyw43::NetDriver<'static>>) -> ! {
-    stack.run().await
+struct AppProps {
+    stack: embassy_net::Stack<'static>,
 }
 
-struct AppProps;
-
 impl AppBuilder for AppProps {
     type PathRouter = impl picoserve::routing::PathRouter;
 
     fn build_app(self) -> picoserve::Router<Self::PathRouter> {
+        let stack = self.stack;
         picoserve::Router::new()
             .route("/", get(|| async move { "Hello World" }))
-            .route("/metrics", get(|| async { Prometheus }))
+            .route(
+                "/metrics",
+                get(|| async { Prometheus { body: metrics::render_metrics(METRICS).await } }),
+            )
+            .route(
+                "/ip",
+                get(move || async move { IpStatus { body: format_ip_status(stack) } }),
+            )
+            .route("/status", get(|| async { Status { body: format_status() } }))
     }
 }
 
@@ -75,8 +77,178 @@ async fn web_task(
     .await
 }
 
+// IP configuration (DHCP with static fallback)
+const USE_DHCP: bool = true;
+const DHCP_TIMEOUT_SECS: u64 = 10;
+
+const FALLBACK_ADDRESS: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 244);
+const FALLBACK_GATEWAY: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 1);
+const FALLBACK_PREFIX_LEN: u8 = 24;
+
+crate::register_gauge!(IP_ADDRESS_BITS);
+crate::register_gauge!(IP_GATEWAY_BITS);
+crate::register_gauge!(DHCP_LEASE_ACTIVE);
+
+fn fallback_config() -> embassy_net::Config {
+    embassy_net::Config::ipv4_static(embassy_net::StaticConfigV4 {
+        address: Ipv4Cidr::new(FALLBACK_ADDRESS, FALLBACK_PREFIX_LEN),
+        dns_servers: heapless::Vec::new(),
+        gateway: Some(FALLBACK_GATEWAY),
+    })
+}
+
+async fn wait_for_config_up(stack: embassy_net::Stack<'static>) {
+    while !stack.is_config_up() {
+        embassy_time::Timer::after(Duration::from_millis(100)).await;
+    }
+}
+
+/// Waits for `stack` to report a usable IPv4 config, then logs it over defmt
+/// and publishes it to the metrics registry. The `/ip` route does not use
+/// this; it reads `stack.config_v4()` on each request.
+async fn record_ip_config(stack: embassy_net::Stack<'static>, dhcp_leased: bool) {
+    wait_for_config_up(stack).await;
+
+    if let Some(cfg) = stack.config_v4() {
+        defmt::info!(
+            "IPv4 config up: address={:?} gateway={:?}",
+            cfg.address.address().to_bits(),
+            cfg.gateway.map(|gw| gw.to_bits()),
+        );
+        IP_ADDRESS_BITS.store(cfg.address.address().to_bits(), core::sync::atomic::Ordering::Relaxed);
+        IP_GATEWAY_BITS.store(
+            cfg.gateway.map(|gw| gw.to_bits()).unwrap_or(0),
+            core::sync::atomic::Ordering::Relaxed,
+        );
+        DHCP_LEASE_ACTIVE.store(dhcp_leased as u32, core::sync::atomic::Ordering::Relaxed);
+    }
+}
+
 // ADC value
-static ADC_VALUE: AtomicU16 = AtomicU16::new(0);
+crate::register_gauge!(ADC_VALUE);
+
+// TCP throughput self-test (modeled on the cyw43 `perf-server` HIL harness)
+const PERF_PORT: u16 = 5000;
+const PERF_SEND_SECS: u64 = 10;
+const PERF_RECEIVE_SECS: u64 = 10;
+const PERF_CMD_RECEIVE: u8 = b'r';
+const PERF_CMD_SEND: u8 = b's';
+const PERF_CMD_ECHO: u8 = b'e';
+
+crate::register_gauge!(TCP_RX_BYTES_PER_SEC);
+crate::register_gauge!(TCP_TX_BYTES_PER_SEC);
+crate::register_gauge!(TCP_ECHO_BYTES_PER_SEC);
+
+#[embassy_executor::task]
+async fn perf_task(stack: embassy_net::Stack<'static>) -> ! {
+    let mut rx_buffer = [0; 4096];
+    let mut tx_buffer = [0; 4096];
+
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+
+        defmt::info!("perf: listening on port {}", PERF_PORT);
+        if let Err(e) = socket.accept(PERF_PORT).await {
+            defmt::warn!("perf: accept error: {:?}", e);
+            continue;
+        }
+
+        let mut cmd = [0u8; 1];
+        if let Err(e) = socket.read_exact(&mut cmd).await {
+            defmt::warn!("perf: failed to read command byte: {:?}", e);
+            socket.close();
+            continue;
+        }
+
+        match cmd[0] {
+            PERF_CMD_RECEIVE => perf_receive(&mut socket).await,
+            PERF_CMD_SEND => perf_send(&mut socket).await,
+            PERF_CMD_ECHO => perf_echo(&mut socket).await,
+            other => defmt::warn!("perf: unknown command byte: {:x}", other),
+        }
+
+        socket.close();
+    }
+}
+
+async fn perf_receive(socket: &mut TcpSocket<'_>) {
+    let mut buf = [0u8; 2048];
+    let mut total: u64 = 0;
+    let start = embassy_time::Instant::now();
+    let deadline = start + Duration::from_secs(PERF_RECEIVE_SECS);
+
+    loop {
+        let remaining = match deadline.checked_duration_since(embassy_time::Instant::now()) {
+            Some(d) if d.as_ticks() > 0 => d,
+            _ => break,
+        };
+
+        match embassy_time::with_timeout(remaining, socket.read(&mut buf)).await {
+            Ok(Ok(0)) => break,
+            Ok(Ok(n)) => total += n as u64,
+            Ok(Err(e)) => {
+                defmt::warn!("perf: receive error: {:?}", e);
+                break;
+            }
+            Err(_) => break,
+        }
+    }
+
+    report_throughput("receive", total, start.elapsed(), &TCP_RX_BYTES_PER_SEC);
+}
+
+async fn perf_send(socket: &mut TcpSocket<'_>) {
+    const CHUNK: [u8; 1024] = [0xAA; 1024];
+
+    let mut total: u64 = 0;
+    let start = embassy_time::Instant::now();
+    let deadline = start + Duration::from_secs(PERF_SEND_SECS);
+
+    while embassy_time::Instant::now() < deadline {
+        match socket.write(&CHUNK).await {
+            Ok(n) => total += n as u64,
+            Err(e) => {
+                defmt::warn!("perf: send error: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    report_throughput("send", total, start.elapsed(), &TCP_TX_BYTES_PER_SEC);
+}
+
+async fn perf_echo(socket: &mut TcpSocket<'_>) {
+    let mut buf = [0u8; 2048];
+    let mut total: u64 = 0;
+    let start = embassy_time::Instant::now();
+
+    loop {
+        match socket.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => {
+                if let Err(e) = socket.write_all(&buf[..n]).await {
+                    defmt::warn!("perf: echo write error: {:?}", e);
+                    break;
+                }
+                total += n as u64;
+            }
+            Err(e) => {
+                defmt::warn!("perf: echo read error: {:?}", e);
+                break;
+            }
+        }
+    }
+
+    report_throughput("echo", total, start.elapsed(), &TCP_ECHO_BYTES_PER_SEC);
+}
+
+fn report_throughput(mode: &str, bytes: u64, elapsed: Duration, gauge: &AtomicU32) {
+    let millis = elapsed.as_millis().max(1);
+    let bits_per_sec = (bytes * 8 * 1000) / millis;
+
+    defmt::info!("perf[{}]: {} bytes in {} ms ({} bit/s)", mode, bytes, millis, bits_per_sec);
+    gauge.store((bits_per_sec / 8) as u32, core::sync::atomic::Ordering::Relaxed);
+}
 
 #[embassy_executor::task]
 async fn read_sensor(mut channel: embassy_rp::adc::Channel<'static>, mut adc: embassy_rp::adc::Adc<'static, Async>) -> ! {
@@ -84,7 +256,7 @@ async fn read_sensor(mut channel: embassy_rp::adc::Channel<'static>, mut adc: em
         // setup ADC for pin 31
         let result = adc.read(&mut channel).await;
         match result {
-            Ok(value) => ADC_VALUE.store(value, core::sync::atomic::Ordering::Relaxed),
+            Ok(value) => ADC_VALUE.store(value as u32, core::sync::atomic::Ordering::Relaxed),
             Err(_) => defmt::warn!("ADC read error"),
         }
         
@@ -92,38 +264,247 @@ async fn read_sensor(mut channel: embassy_rp::adc::Channel<'static>, mut adc: em
     }
 }
 
-struct Prometheus;
+/// The metrics registry. Adding a sensor is one [`crate::register_gauge!`]
+/// declaration plus one row in each transport's table below — `Prometheus`
+/// never needs to change.
+///
+/// The `wifi_*` gauges only exist behind the `wifi` feature (they're backed by
+/// atomics declared in [`net::wifi`]), so the table itself is duplicated per
+/// transport rather than assembled piecemeal at const-eval time.
+#[cfg(feature = "wifi")]
+static METRICS: &[Metric] = &[
+    Metric::gauge(
+        "adc_value",
+        "The value read from the ADC",
+        "{sensor=\"MQ-2\"}",
+        &ADC_VALUE,
+    ),
+    Metric::gauge(
+        "tcp_rx_bytes_per_sec",
+        "Measured TCP receive throughput from the last perf self-test run",
+        "",
+        &TCP_RX_BYTES_PER_SEC,
+    ),
+    Metric::gauge(
+        "tcp_tx_bytes_per_sec",
+        "Measured TCP send throughput from the last perf self-test run",
+        "",
+        &TCP_TX_BYTES_PER_SEC,
+    ),
+    Metric::gauge(
+        "tcp_echo_bytes_per_sec",
+        "Measured TCP echo throughput from the last perf self-test run",
+        "",
+        &TCP_ECHO_BYTES_PER_SEC,
+    ),
+    Metric::gauge(
+        "ip_address_bits",
+        "The device's IPv4 address, as a big-endian u32",
+        "",
+        &IP_ADDRESS_BITS,
+    ),
+    Metric::gauge(
+        "ip_gateway_bits",
+        "The configured IPv4 gateway, as a big-endian u32",
+        "",
+        &IP_GATEWAY_BITS,
+    ),
+    Metric::gauge(
+        "dhcp_lease_active",
+        "1 if the current IPv4 config came from DHCP, 0 if static fallback",
+        "",
+        &DHCP_LEASE_ACTIVE,
+    ),
+    Metric::signed_gauge(
+        "wifi_rssi_dbm",
+        "Last-measured cyw43 RSSI, in dBm",
+        "",
+        &net::wifi::WIFI_RSSI_DBM,
+    ),
+    Metric::gauge(
+        "wifi_connected",
+        "1 if the cyw43 radio is currently joined to the configured network",
+        "",
+        &net::wifi::WIFI_CONNECTED,
+    ),
+];
 
-const HEADER: &str = "# HELP adc_value The value read from the ADC\n# TYPE adc_value gauge\n";
-const METRICS: [&str; 1] = [
-    "adc_value{sensor=\"MQ-2\"} ",
+#[cfg(feature = "wifi")]
+const _: () = assert!(
+    metrics::worst_case_len(METRICS) <= metrics::MAX_METRICS_LEN,
+    "METRICS table may overflow MAX_METRICS_LEN; bump the latter"
+);
+
+#[cfg(not(feature = "wifi"))]
+static METRICS: &[Metric] = &[
+    Metric::gauge(
+        "adc_value",
+        "The value read from the ADC",
+        "{sensor=\"MQ-2\"}",
+        &ADC_VALUE,
+    ),
+    Metric::gauge(
+        "tcp_rx_bytes_per_sec",
+        "Measured TCP receive throughput from the last perf self-test run",
+        "",
+        &TCP_RX_BYTES_PER_SEC,
+    ),
+    Metric::gauge(
+        "tcp_tx_bytes_per_sec",
+        "Measured TCP send throughput from the last perf self-test run",
+        "",
+        &TCP_TX_BYTES_PER_SEC,
+    ),
+    Metric::gauge(
+        "tcp_echo_bytes_per_sec",
+        "Measured TCP echo throughput from the last perf self-test run",
+        "",
+        &TCP_ECHO_BYTES_PER_SEC,
+    ),
+    Metric::gauge(
+        "ip_address_bits",
+        "The device's IPv4 address, as a big-endian u32",
+        "",
+        &IP_ADDRESS_BITS,
+    ),
+    Metric::gauge(
+        "ip_gateway_bits",
+        "The configured IPv4 gateway, as a big-endian u32",
+        "",
+        &IP_GATEWAY_BITS,
+    ),
+    Metric::gauge(
+        "dhcp_lease_active",
+        "1 if the current IPv4 config came from DHCP, 0 if static fallback",
+        "",
+        &DHCP_LEASE_ACTIVE,
+    ),
 ];
 
+#[cfg(not(feature = "wifi"))]
+const _: () = assert!(
+    metrics::worst_case_len(METRICS) <= metrics::MAX_METRICS_LEN,
+    "METRICS table may overflow MAX_METRICS_LEN; bump the latter"
+);
+
+/// A pre-rendered exposition body. Rendered exactly once by the route
+/// handler via [`metrics::render_metrics`] so `content_length` and
+/// `write_content` always agree, even though the backing atomics keep
+/// changing between the two calls.
+struct Prometheus {
+    body: heapless::Vec<u8, { metrics::MAX_METRICS_LEN }>,
+}
+
 impl picoserve::response::Content for Prometheus {
     fn content_type(&self) -> &'static str {
         "text/plain; version=0.0.4"
     }
 
     fn content_length(&self) -> usize {
-        HEADER.len() + METRICS.iter().map(|m| m.len()).sum::<usize>() + 5
+        self.body.len()
     }
 
-     fn write_content<W: embedded_io_async::Write>(self, mut writer: W) -> impl Future<Output = Result<(), W::Error>> {
-        async move {
-            writer.write_all(HEADER.as_bytes()).await?;
-            for metric in METRICS {
-                writer.write_all(metric.as_bytes()).await?;
-            }
+    fn write_content<W: embedded_io_async::Write>(self, mut writer: W) -> impl Future<Output = Result<(), W::Error>> {
+        async move { writer.write_all(&self.body).await }
+    }
+}
 
-            let adc_value = ADC_VALUE.load(core::sync::atomic::Ordering::Relaxed);
-            let mut value = heapless::String::<32>::new();
-            write!(value, "{:05}", adc_value).unwrap();
+/// Pre-rendered by the route handler so `content_length` and `write_content`
+/// read the same snapshot instead of re-formatting (and re-reading
+/// `stack.config_v4()`) twice.
+struct IpStatus {
+    body: heapless::String<192>,
+}
 
-            writer.write_all(value.as_bytes()).await?;
+impl picoserve::response::Content for IpStatus {
+    fn content_type(&self) -> &'static str {
+        "text/plain"
+    }
+
+    fn content_length(&self) -> usize {
+        self.body.len()
+    }
 
-            Ok(())
+    fn write_content<W: embedded_io_async::Write>(self, mut writer: W) -> impl Future<Output = Result<(), W::Error>> {
+        async move { writer.write_all(self.body.as_bytes()).await }
+    }
+}
+
+fn format_ip_status(stack: embassy_net::Stack<'static>) -> heapless::String<192> {
+    let mut out = heapless::String::new();
+    match stack.config_v4() {
+        Some(cfg) => {
+            let _ = write!(out, "address: {}\n", cfg.address);
+            match cfg.gateway {
+                Some(gw) => {
+                    let _ = write!(out, "gateway: {}\n", gw);
+                }
+                None => {
+                    let _ = write!(out, "gateway: none\n");
+                }
+            }
+            let _ = write!(out, "dns_servers: ");
+            for (i, dns) in cfg.dns_servers.iter().enumerate() {
+                if i > 0 {
+                    let _ = write!(out, ", ");
+                }
+                let _ = write!(out, "{}", dns);
+            }
+            let _ = write!(out, "\n");
+        }
+        None => {
+            let _ = write!(out, "no IPv4 config\n");
         }
     }
+    out
+}
+
+/// Pre-rendered by the route handler so `content_length` and `write_content`
+/// agree even though `uptime_ms` and the sensor/link atomics it reads keep
+/// changing between calls.
+struct Status {
+    body: heapless::String<192>,
+}
+
+impl picoserve::response::Content for Status {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn content_length(&self) -> usize {
+        self.body.len()
+    }
+
+    fn write_content<W: embedded_io_async::Write>(self, mut writer: W) -> impl Future<Output = Result<(), W::Error>> {
+        async move { writer.write_all(self.body.as_bytes()).await }
+    }
+}
+
+/// A point-in-time snapshot of link quality and sensor state, as JSON. Kept
+/// alongside `/metrics` for callers that want a single cheap poll rather than
+/// a full Prometheus scrape.
+fn format_status() -> heapless::String<192> {
+    use core::sync::atomic::Ordering;
+
+    let mut out = heapless::String::new();
+
+    #[cfg(feature = "wifi")]
+    let (wifi_connected, wifi_rssi_dbm) = (
+        net::wifi::WIFI_CONNECTED.load(Ordering::Relaxed) != 0,
+        net::wifi::WIFI_RSSI_DBM.load(Ordering::Relaxed) as i32,
+    );
+    #[cfg(not(feature = "wifi"))]
+    let (wifi_connected, wifi_rssi_dbm) = (false, 0i32);
+
+    let _ = write!(
+        out,
+        "{{\"wifi_connected\":{},\"wifi_rssi_dbm\":{},\"uptime_ms\":{},\"adc_value\":{}}}",
+        wifi_connected,
+        wifi_rssi_dbm,
+        embassy_time::Instant::now().as_millis(),
+        ADC_VALUE.load(Ordering::Relaxed),
+    );
+    out
 }
 
 #[embassy_executor::main]
@@ -134,69 +515,75 @@ async fn main(spawner: embassy_executor::Spawner) {
     let adc = embassy_rp::adc::Adc::new(p.ADC, Irqs, embassy_rp::adc::Config::default());
     spawner.must_spawn(read_sensor(channel, adc));
 
-    let fw = include_bytes!("../../cyw43-firmware/43439A0.bin");
-    let clm = include_bytes!("../../cyw43-firmware/43439A0_clm.bin");
-
-    let pwr = Output::new(p.PIN_23, Level::Low);
-    let cs = Output::new(p.PIN_25, Level::High);
-    let mut pio = Pio::new(p.PIO0, Irqs);
-    let spi = cyw43_pio::PioSpi::new(
-        &mut pio.common,
-        pio.sm0,
-        cyw43_pio::DEFAULT_CLOCK_DIVIDER,
-        pio.irq0,
-        cs,
-        p.PIN_24,
-        p.PIN_29,
-        p.DMA_CH0,
+    let config = if USE_DHCP {
+        embassy_net::Config::dhcpv4(Default::default())
+    } else {
+        fallback_config()
+    };
+    let stack_resources = make_static!(
+        embassy_net::StackResources::<WEB_TASK_POOL_SIZE>,
+        embassy_net::StackResources::new()
     );
 
-    let state = make_static!(cyw43::State, cyw43::State::new());
-    let (net_device, mut control, runner) = cyw43::new(state, pwr, spi, fw).await;
-    spawner.must_spawn(wifi_task(runner));
-
-    control.init(clm).await;
-    control.set_power_management(cyw43::PowerManagementMode::None).await;
-
-    let config = embassy_net::Config::ipv4_static(embassy_net::StaticConfigV4 {
-       address: Ipv4Cidr::new(Ipv4Addr::new(192, 168, 1, 244), 24),
-       dns_servers: heapless::Vec::new(),
-       gateway: Some(Ipv4Addr::new(192, 168, 1, 1)),
-    });
-    let (stack, runner) = embassy_net::new(
-        net_device,
+    #[cfg(feature = "wifi")]
+    let (stack, mut link) = net::init(
+        spawner,
+        net::wifi::WifiResources {
+            pwr: p.PIN_23,
+            cs: p.PIN_25,
+            clk: p.PIN_24,
+            dat: p.PIN_29,
+            pio: p.PIO0,
+            dma: p.DMA_CH0,
+            flash: p.FLASH,
+            flash_dma: p.DMA_CH1,
+        },
         config,
-        make_static!(
-            embassy_net::StackResources::<WEB_TASK_POOL_SIZE>,
-            embassy_net::StackResources::new()
-        ),
-        embassy_rp::clocks::RoscRng.gen(),
-    );
-
-    spawner.must_spawn(net_task(runner));
-
-
-    
-    loop {
-        match control.join(core::option_env!("WIFI_NETWORK").unwrap(), JoinOptions::new(core::option_env!("WIFI_PASSWORD").unwrap().as_bytes())).await {
-            Ok(_) => {
-                defmt::info!("Connected to WiFi");
-                break;
-            }
-            Err(e) => {
-                defmt::error!("Failed to connect to WiFi: STATUS = {:?}", e.status);
-                embassy_time::Timer::after(Duration::from_secs(1)).await;
+        stack_resources,
+    )
+    .await;
+
+    #[cfg(feature = "ethernet")]
+    let (stack, mut link) = net::init(
+        spawner,
+        net::w5500::W5500Resources {
+            spi: p.SPI0,
+            clk: p.PIN_18,
+            mosi: p.PIN_19,
+            miso: p.PIN_16,
+            cs: p.PIN_17,
+            reset: p.PIN_20,
+            interrupt: p.PIN_21,
+            dma_tx: p.DMA_CH0,
+            dma_rx: p.DMA_CH1,
+        },
+        config,
+        stack_resources,
+    )
+    .await;
+
+    spawner.must_spawn(perf_task(stack));
+
+    let dhcp_leased = if USE_DHCP {
+        match embassy_time::with_timeout(Duration::from_secs(DHCP_TIMEOUT_SECS), wait_for_config_up(stack)).await {
+            Ok(()) => true,
+            Err(_) => {
+                defmt::warn!("DHCP timed out after {}s, falling back to static config", DHCP_TIMEOUT_SECS);
+                stack.set_config_v4(embassy_net::ConfigV4::Static(embassy_net::StaticConfigV4 {
+                    address: Ipv4Cidr::new(FALLBACK_ADDRESS, FALLBACK_PREFIX_LEN),
+                    dns_servers: heapless::Vec::new(),
+                    gateway: Some(FALLBACK_GATEWAY),
+                }));
+                false
             }
-            
         }
-    }
-    
-    while !stack.is_config_up() {
-        defmt::info!("Waiting for DHCP configuration...");
-        embassy_time::Timer::after(Duration::from_secs(1)).await;
-    }
+    } else {
+        false
+    };
+
+    record_ip_config(stack, dhcp_leased).await;
 
-    let app = make_static!(AppRouter<AppProps>, AppProps.build_app());
+    let app = make_static!(AppRouter<AppProps>, AppProps { stack }.build_app());
 
     let config = make_static!(
         picoserve::Config::<Duration>,
@@ -216,6 +603,5 @@ async fn main(spawner: embassy_executor::Spawner) {
     }
 
     // Turn on the LED to indicate that the server is running
-    control.gpio_set(0, true).await;
-
+    link.set_status_led(true).await;
 }
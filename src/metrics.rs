@@ -0,0 +1,187 @@
+//! A small Prometheus-style metrics registry.
+//!
+//! Sensors declare a backing `AtomicU32` with [`register_gauge!`] and add one
+//! row to their `static METRICS: &[Metric]` table; [`write_metrics`] walks
+//! that table and emits the `# HELP`/`# TYPE` preamble once per family plus
+//! one sample line per metric, so exposing a new sensor never means editing
+//! the exporter itself. The atomics are free-running (ADC/RSSI/throughput
+//! tasks update them on their own schedules), so `Content::content_length`
+//! and `Content::write_content` must never sample them independently — each
+//! read could observe a different value and desync the advertised length
+//! from the body. [`render_metrics`] renders the whole body into a buffer
+//! exactly once; callers hand the same snapshot to both `Content` methods.
+
+use core::fmt::Write as _;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+pub enum MetricKind {
+    Gauge,
+}
+
+impl MetricKind {
+    const fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Gauge => "gauge",
+        }
+    }
+}
+
+pub struct Metric {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: MetricKind,
+    /// Prometheus label set, including braces, e.g. `{sensor="MQ-2"}`, or `""`.
+    pub labels: &'static str,
+    pub value: &'static AtomicU32,
+    /// Whether `value`'s bits should be read back as an `i32` (e.g. RSSI in
+    /// dBm) rather than a `u32`.
+    signed: bool,
+}
+
+impl Metric {
+    pub const fn gauge(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static str,
+        value: &'static AtomicU32,
+    ) -> Self {
+        Self {
+            name,
+            help,
+            kind: MetricKind::Gauge,
+            labels,
+            value,
+            signed: false,
+        }
+    }
+
+    /// Like [`Metric::gauge`], but `value`'s bits are reinterpreted as an
+    /// `i32` when rendered, for metrics like RSSI that can go negative.
+    pub const fn signed_gauge(
+        name: &'static str,
+        help: &'static str,
+        labels: &'static str,
+        value: &'static AtomicU32,
+    ) -> Self {
+        Self {
+            name,
+            help,
+            kind: MetricKind::Gauge,
+            labels,
+            value,
+            signed: true,
+        }
+    }
+
+    fn write_value(&self, out: &mut heapless::String<64>) {
+        let bits = self.value.load(Ordering::Relaxed);
+        if self.signed {
+            let _ = write!(out, "{}", bits as i32);
+        } else {
+            let _ = write!(out, "{}", bits);
+        }
+    }
+}
+
+/// Declares the `AtomicU32` backing store for a registered gauge. Pair with a
+/// [`Metric::gauge`] entry in the crate's `METRICS` table.
+#[macro_export]
+macro_rules! register_gauge {
+    ($vis:vis $name:ident) => {
+        $vis static $name: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+    };
+}
+
+/// Writes `metrics` as Prometheus exposition text.
+pub async fn write_metrics<W: embedded_io_async::Write>(
+    metrics: &[Metric],
+    writer: &mut W,
+) -> Result<(), W::Error> {
+    let mut last_name: Option<&str> = None;
+
+    for metric in metrics {
+        if last_name != Some(metric.name) {
+            let mut header = heapless::String::<192>::new();
+            let _ = write!(
+                header,
+                "# HELP {} {}\n# TYPE {} {}\n",
+                metric.name,
+                metric.help,
+                metric.name,
+                metric.kind.as_str()
+            );
+            writer.write_all(header.as_bytes()).await?;
+            last_name = Some(metric.name);
+        }
+
+        let mut line = heapless::String::<64>::new();
+        let _ = write!(line, "{}{} ", metric.name, metric.labels);
+        metric.write_value(&mut line);
+        let _ = write!(line, "\n");
+        writer.write_all(line.as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+/// Upper bound on the rendered exposition text. Sized for the full `wifi`
+/// build's table (9 gauges, ~1.2 KB with realistic values after DHCP and a
+/// perf run) plus headroom; bump this whenever `METRICS` grows enough to
+/// need more room. [`VecWriter`] never overruns the buffer, but relies on
+/// this staying ahead of the real body — it has no way to recover a scrape
+/// that's already too big to fit.
+pub const MAX_METRICS_LEN: usize = 2048;
+
+struct VecWriter<'a>(&'a mut heapless::Vec<u8, MAX_METRICS_LEN>);
+
+impl embedded_io_async::ErrorType for VecWriter<'_> {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_io_async::Write for VecWriter<'_> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = buf.len().min(self.0.capacity() - self.0.len());
+        let _ = self.0.extend_from_slice(&buf[..n]);
+        // Report the whole chunk as written even if the tail was dropped:
+        // `write_all` treats `Ok(0)` as a broken writer and panics, and once
+        // `MAX_METRICS_LEN` is exceeded there's nothing useful left to do but
+        // silently truncate rather than crash the device on a scrape.
+        Ok(buf.len())
+    }
+}
+
+/// Conservative worst-case byte length of [`write_metrics`]'s output for
+/// `metrics`, assuming every value renders at its widest (e.g.
+/// `-2147483648` for a signed gauge) and ignoring the `# HELP`/`# TYPE`
+/// dedup for repeated names, so it can only ever overestimate. Pair with a
+/// `const` assertion against [`MAX_METRICS_LEN`] wherever a `METRICS` table
+/// is declared, so growing the table past the buffer fails the build
+/// instead of panicking a live scrape.
+pub const fn worst_case_len(metrics: &[Metric]) -> usize {
+    const MAX_VALUE_DIGITS: usize = 11; // "-2147483648"
+
+    let mut total = 0;
+    let mut i = 0;
+    while i < metrics.len() {
+        let m = &metrics[i];
+        // "# HELP {name} {help}\n# TYPE {name} {kind}\n"
+        total += "# HELP  \n# TYPE  \n".len()
+            + 2 * m.name.len()
+            + m.help.len()
+            + m.kind.as_str().len();
+        // "{name}{labels} {value}\n"
+        total += m.name.len() + m.labels.len() + 1 + MAX_VALUE_DIGITS + 1;
+        i += 1;
+    }
+    total
+}
+
+/// Renders `metrics` as Prometheus exposition text exactly once. Take the
+/// single returned snapshot and reuse it for both `Content::content_length`
+/// and `Content::write_content` so the two can never disagree.
+pub async fn render_metrics(metrics: &[Metric]) -> heapless::Vec<u8, MAX_METRICS_LEN> {
+    let mut out = heapless::Vec::new();
+    let mut writer = VecWriter(&mut out);
+    let _ = write_metrics(metrics, &mut writer).await;
+    out
+}
@@ -0,0 +1,27 @@
+//! Transport-agnostic network bring-up.
+//!
+//! `main` selects a transport at compile time via the `wifi` (default) and
+//! `ethernet` feature flags, which are mutually exclusive. Whichever one is
+//! active hands back a plain `embassy_net::Stack` (each transport spawns its
+//! own `net_task` for its driver), so `web_task`, the ADC task, and the
+//! metrics endpoint never need to know whether they're running over the
+//! onboard cyw43 radio or a wired W5500.
+
+#[cfg(feature = "wifi")]
+pub mod wifi;
+#[cfg(feature = "wifi")]
+pub use wifi::{init, Control};
+
+#[cfg(feature = "ethernet")]
+pub mod w5500;
+#[cfg(feature = "ethernet")]
+pub use w5500::{init, Control};
+
+/// Surface common to every transport's link handle: whatever `main` needs to
+/// do after the web server is up, regardless of which radio/PHY backs it.
+pub trait LinkControl {
+    /// Drives the board's status LED. On a Pico W this is wired through the
+    /// cyw43 chip even when it isn't doing WiFi work; the W5500 transport
+    /// drives no LED, so its implementation does nothing.
+    async fn set_status_led(&mut self, on: bool);
+}
@@ -0,0 +1,278 @@
+//! Wired Ethernet transport: a WIZnet W5500 driven in MACRAW mode over
+//! `embedded-hal-async` SPI, bridged into `embassy_net` via
+//! `embassy-net-driver-channel` the same way `cyw43`'s net device is wired up
+//! internally. Lets boards that pair a bare RP2040 with a W5500 module run
+//! the same picoserve app, ADC task, and metrics endpoint as the WiFi build.
+
+use embassy_executor::Spawner;
+use embassy_net::{Stack, StackResources};
+use embassy_net_driver_channel as ch;
+use embassy_rp::gpio::{Input, Level, Output, Pull};
+use embassy_rp::peripherals::{DMA_CH0, DMA_CH1, PIN_16, PIN_17, PIN_18, PIN_19, PIN_20, PIN_21, SPI0};
+use embassy_rp::spi::{Async, Config as SpiConfig, Spi};
+use embassy_time::{Duration, Timer};
+use embedded_hal_async::spi::SpiBus;
+use rand::Rng;
+
+use crate::WEB_TASK_POOL_SIZE;
+
+const MTU: usize = 1514;
+
+/// Pins and peripherals the W5500 module needs; carved out of
+/// `embassy_rp::Peripherals` by `main`.
+pub struct W5500Resources {
+    pub spi: SPI0,
+    pub clk: PIN_18,
+    pub mosi: PIN_19,
+    pub miso: PIN_16,
+    pub cs: PIN_17,
+    pub reset: PIN_20,
+    pub interrupt: PIN_21,
+    pub dma_tx: DMA_CH0,
+    pub dma_rx: DMA_CH1,
+}
+
+pub struct Control;
+
+impl super::LinkControl for Control {
+    async fn set_status_led(&mut self, _on: bool) {
+        // No user LED is wired to the RP2040 on a bare Pico + W5500 module.
+    }
+}
+
+// W5500 common register block (BSB = 0b00000).
+const BLOCK_COMMON: u8 = 0x00;
+const REG_MR: u16 = 0x0000;
+const REG_SHAR: u16 = 0x0009;
+const REG_PHYCFGR: u16 = 0x002E;
+
+// Socket 0 register block (BSB = 0b00001) and its buffers.
+const BLOCK_SOCKET0_REG: u8 = 0x01;
+const BLOCK_SOCKET0_TX: u8 = 0x02;
+const BLOCK_SOCKET0_RX: u8 = 0x03;
+const REG_SN_MR: u16 = 0x0000;
+const REG_SN_CR: u16 = 0x0001;
+const REG_SN_SR: u16 = 0x0003;
+const REG_SN_RX_RSR: u16 = 0x0026;
+const REG_SN_RX_RD: u16 = 0x0028;
+const REG_SN_TX_FSR: u16 = 0x0020;
+const REG_SN_TX_WR: u16 = 0x0024;
+const REG_SN_RXBUF_SIZE: u16 = 0x001E;
+const REG_SN_TXBUF_SIZE: u16 = 0x001F;
+
+const SN_MR_MACRAW: u8 = 0x04;
+const SN_CR_OPEN: u8 = 0x01;
+const SN_CR_SEND: u8 = 0x20;
+const SN_CR_RECV: u8 = 0x40;
+const SOCK_MACRAW: u8 = 0x42;
+
+const TX_BUF_BASE: u16 = 0x0000;
+const RX_BUF_BASE: u16 = 0x0000;
+/// Per-socket buffer size in KiB programmed into `Sn_RXBUF_SIZE`/
+/// `Sn_TXBUF_SIZE` during [`init`]. The W5500 resets to 2 KiB/socket, but the
+/// ring math below relies on it, so it's set explicitly rather than assumed.
+const SOCKET_BUF_SIZE_KB: u8 = 2;
+const SOCKET_BUF_SIZE: u16 = SOCKET_BUF_SIZE_KB as u16 * 1024;
+
+struct Raw<'d> {
+    spi: Spi<'d, SPI0, Async>,
+    cs: Output<'d>,
+}
+
+impl<'d> Raw<'d> {
+    fn control_byte(block: u8, write: bool) -> u8 {
+        (block << 3) | ((write as u8) << 2)
+    }
+
+    async fn read(&mut self, block: u8, addr: u16, buf: &mut [u8]) {
+        self.cs.set_low();
+        let header = [
+            (addr >> 8) as u8,
+            (addr & 0xFF) as u8,
+            Self::control_byte(block, false),
+        ];
+        let _ = self.spi.write(&header).await;
+        let _ = self.spi.read(buf).await;
+        self.cs.set_high();
+    }
+
+    /// Like [`Raw::read`], but `offset` is a byte offset into a socket's RX/TX
+    /// ring buffer rather than an absolute chip address: a single SPI burst
+    /// doesn't auto-wrap at the `SOCKET_BUF_SIZE` boundary the way the ring
+    /// pointers do, so a read that straddles it has to be split into a head
+    /// segment ending at the boundary and a wrapped segment starting again at
+    /// `base`.
+    async fn read_ring(&mut self, block: u8, base: u16, offset: u16, buf: &mut [u8]) {
+        let start = offset % SOCKET_BUF_SIZE;
+        let until_wrap = (SOCKET_BUF_SIZE - start) as usize;
+
+        if buf.len() <= until_wrap {
+            self.read(block, base + start, buf).await;
+        } else {
+            let (head, tail) = buf.split_at_mut(until_wrap);
+            self.read(block, base + start, head).await;
+            self.read(block, base, tail).await;
+        }
+    }
+
+    /// Ring-aware counterpart to [`Raw::read_ring`] for writes.
+    async fn write_ring(&mut self, block: u8, base: u16, offset: u16, data: &[u8]) {
+        let start = offset % SOCKET_BUF_SIZE;
+        let until_wrap = (SOCKET_BUF_SIZE - start) as usize;
+
+        if data.len() <= until_wrap {
+            self.write(block, base + start, data).await;
+        } else {
+            let (head, tail) = data.split_at(until_wrap);
+            self.write(block, base + start, head).await;
+            self.write(block, base, tail).await;
+        }
+    }
+
+    async fn write(&mut self, block: u8, addr: u16, data: &[u8]) {
+        self.cs.set_low();
+        let header = [
+            (addr >> 8) as u8,
+            (addr & 0xFF) as u8,
+            Self::control_byte(block, true),
+        ];
+        let _ = self.spi.write(&header).await;
+        let _ = self.spi.write(data).await;
+        self.cs.set_high();
+    }
+
+    async fn read_u8(&mut self, block: u8, addr: u16) -> u8 {
+        let mut buf = [0u8; 1];
+        self.read(block, addr, &mut buf).await;
+        buf[0]
+    }
+
+    async fn write_u8(&mut self, block: u8, addr: u16, value: u8) {
+        self.write(block, addr, &[value]).await;
+    }
+
+    async fn read_u16(&mut self, block: u8, addr: u16) -> u16 {
+        let mut buf = [0u8; 2];
+        self.read(block, addr, &mut buf).await;
+        u16::from_be_bytes(buf)
+    }
+
+    async fn socket_command(&mut self, cmd: u8) {
+        self.write_u8(BLOCK_SOCKET0_REG, REG_SN_CR, cmd).await;
+        while self.read_u8(BLOCK_SOCKET0_REG, REG_SN_CR).await != 0 {
+            Timer::after(Duration::from_micros(50)).await;
+        }
+    }
+}
+
+#[embassy_executor::task]
+async fn w5500_task(mut raw: Raw<'static>, mut runner: ch::Runner<'static, MTU>) -> ! {
+    let (mut state_runner, mut rx_chan, mut tx_chan) = runner.split();
+    // `init` already waited on PHYCFGR's link bit before spawning this task,
+    // so the driver can come up immediately; without this, `embassy_net`
+    // never sees the link as up and DHCP/traffic never start.
+    state_runner.set_link_state(embassy_net::driver::LinkState::Up);
+
+    loop {
+        let rx_ready = raw.read_u16(BLOCK_SOCKET0_REG, REG_SN_RX_RSR).await;
+        if rx_ready >= 2 {
+            let rx_rd = raw.read_u16(BLOCK_SOCKET0_REG, REG_SN_RX_RD).await;
+            let mut header = [0u8; 2];
+            raw.read_ring(BLOCK_SOCKET0_RX, RX_BUF_BASE, rx_rd, &mut header)
+                .await;
+            let frame_len = (u16::from_be_bytes(header) as usize).saturating_sub(2).min(MTU);
+
+            if let Some(buf) = rx_chan.try_rx_buf() {
+                raw.read_ring(
+                    BLOCK_SOCKET0_RX,
+                    RX_BUF_BASE,
+                    rx_rd.wrapping_add(2),
+                    &mut buf[..frame_len],
+                )
+                .await;
+                rx_chan.rx_done(frame_len);
+            }
+
+            let new_rd = rx_rd.wrapping_add(2 + frame_len as u16);
+            raw.write(BLOCK_SOCKET0_REG, REG_SN_RX_RD, &new_rd.to_be_bytes()).await;
+            raw.socket_command(SN_CR_RECV).await;
+        }
+
+        if let Some(frame) = tx_chan.try_tx_buf() {
+            let tx_wr = raw.read_u16(BLOCK_SOCKET0_REG, REG_SN_TX_WR).await;
+            raw.write_ring(BLOCK_SOCKET0_TX, TX_BUF_BASE, tx_wr, frame).await;
+            let new_wr = tx_wr.wrapping_add(frame.len() as u16);
+            raw.write(BLOCK_SOCKET0_REG, REG_SN_TX_WR, &new_wr.to_be_bytes()).await;
+            raw.socket_command(SN_CR_SEND).await;
+            tx_chan.tx_done();
+        }
+
+        Timer::after(Duration::from_millis(1)).await;
+    }
+}
+
+pub async fn init(
+    spawner: Spawner,
+    resources: W5500Resources,
+    config: embassy_net::Config,
+    stack_resources: &'static mut StackResources<WEB_TASK_POOL_SIZE>,
+) -> (Stack<'static>, Control) {
+    let mut reset = Output::new(resources.reset, Level::Low);
+    let _interrupt = Input::new(resources.interrupt, Pull::Up);
+
+    let spi = Spi::new(
+        resources.spi,
+        resources.clk,
+        resources.mosi,
+        resources.miso,
+        resources.dma_tx,
+        resources.dma_rx,
+        SpiConfig::default(),
+    );
+    let cs = Output::new(resources.cs, Level::High);
+    let mut raw = Raw { spi, cs };
+
+    reset.set_low();
+    Timer::after(Duration::from_millis(1)).await;
+    reset.set_high();
+    Timer::after(Duration::from_millis(50)).await;
+
+    raw.write_u8(BLOCK_COMMON, REG_MR, 0x00).await;
+
+    let mac = [0x02, 0x00, 0x00, 0x70, 0x69, 0x77]; // locally-administered, "piw"
+    raw.write(BLOCK_COMMON, REG_SHAR, &mac).await;
+
+    raw.write_u8(BLOCK_SOCKET0_REG, REG_SN_RXBUF_SIZE, SOCKET_BUF_SIZE_KB).await;
+    raw.write_u8(BLOCK_SOCKET0_REG, REG_SN_TXBUF_SIZE, SOCKET_BUF_SIZE_KB).await;
+    raw.write_u8(BLOCK_SOCKET0_REG, REG_SN_MR, SN_MR_MACRAW).await;
+    raw.socket_command(SN_CR_OPEN).await;
+
+    while raw.read_u8(BLOCK_SOCKET0_REG, REG_SN_SR).await != SOCK_MACRAW {
+        Timer::after(Duration::from_millis(10)).await;
+    }
+
+    while raw.read_u8(BLOCK_COMMON, REG_PHYCFGR).await & 0x01 == 0 {
+        defmt::info!("Waiting for W5500 link up...");
+        Timer::after(Duration::from_millis(100)).await;
+    }
+    defmt::info!("W5500 link up");
+
+    let state = picoserve::make_static!(ch::State<MTU, 4, 4>, ch::State::new());
+    let (runner, device) = ch::new(state, embassy_net::driver::HardwareAddress::Ethernet(mac));
+    spawner.must_spawn(w5500_task(raw, runner));
+
+    let (stack, net_runner) = embassy_net::new(
+        device,
+        config,
+        stack_resources,
+        embassy_rp::clocks::RoscRng.gen(),
+    );
+    spawner.must_spawn(net_task(net_runner));
+
+    (stack, Control)
+}
+
+#[embassy_executor::task]
+async fn net_task(mut runner: embassy_net::Runner<'static, ch::Device<'static, MTU>>) -> ! {
+    runner.run().await
+}
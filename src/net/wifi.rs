@@ -0,0 +1,233 @@
+//! WiFi transport: cyw43 bring-up, BLE-provisioned credentials, and the
+//! `control.join` retry loop. This is the default transport on a Pico W.
+
+use cyw43::JoinOptions;
+use cyw43_pio::PioSpi;
+use embassy_executor::Spawner;
+use embassy_net::{Stack, StackResources};
+use embassy_rp::flash::{Async, Flash};
+use embassy_rp::gpio::{Level, Output};
+use embassy_rp::peripherals::{DMA_CH0, DMA_CH1, FLASH, PIN_23, PIN_24, PIN_25, PIN_29, PIO0};
+use embassy_rp::pio::Pio;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_time::Duration;
+use rand::Rng;
+
+use crate::provisioning::{self, WifiCredentials};
+use crate::WEB_TASK_POOL_SIZE;
+
+/// Number of consecutive `control.join` failures before falling back to BLE
+/// provisioning instead of retrying the same (possibly stale) credentials.
+const MAX_JOIN_ATTEMPTS_BEFORE_PROVISIONING: u32 = 5;
+
+/// How often [`link_monitor_task`] samples RSSI/connection state and checks
+/// whether the link needs to be rejoined.
+const LINK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+crate::register_gauge!(pub WIFI_RSSI_DBM);
+crate::register_gauge!(pub WIFI_CONNECTED);
+
+/// The last credentials we successfully joined with, kept around so
+/// [`link_monitor_task`] can rejoin after a dropped link without re-running
+/// BLE provisioning.
+static CREDENTIALS: Mutex<CriticalSectionRawMutex, Option<WifiCredentials>> = Mutex::new(None);
+
+/// Pins and peripherals the cyw43 radio needs; carved out of
+/// `embassy_rp::Peripherals` by `main` so this module stays board-agnostic
+/// about everything else.
+pub struct WifiResources {
+    pub pwr: PIN_23,
+    pub cs: PIN_25,
+    pub clk: PIN_24,
+    pub dat: PIN_29,
+    pub pio: PIO0,
+    pub dma: DMA_CH0,
+    pub flash: FLASH,
+    pub flash_dma: DMA_CH1,
+}
+
+pub struct Control {
+    inner: &'static Mutex<CriticalSectionRawMutex, cyw43::Control<'static>>,
+}
+
+impl super::LinkControl for Control {
+    async fn set_status_led(&mut self, on: bool) {
+        self.inner.lock().await.gpio_set(0, on).await;
+    }
+}
+
+#[embassy_executor::task]
+async fn wifi_task(
+    runner: cyw43::Runner<'static, Output<'static>, PioSpi<'static, PIO0, 0, DMA_CH0>>,
+) -> ! {
+    runner.run().await
+}
+
+#[embassy_executor::task]
+async fn net_task(mut runner: embassy_net::Runner<'static, cyw43::NetDriver<'static>>) -> ! {
+    runner.run().await
+}
+
+pub async fn init(
+    spawner: Spawner,
+    resources: WifiResources,
+    config: embassy_net::Config,
+    stack_resources: &'static mut StackResources<WEB_TASK_POOL_SIZE>,
+) -> (Stack<'static>, Control) {
+    let fw = include_bytes!("../../../cyw43-firmware/43439A0.bin");
+    let clm = include_bytes!("../../../cyw43-firmware/43439A0_clm.bin");
+    let btfw = include_bytes!("../../../cyw43-firmware/43439A0_btfw.bin");
+
+    let pwr = Output::new(resources.pwr, Level::Low);
+    let cs = Output::new(resources.cs, Level::High);
+    let mut pio = Pio::new(resources.pio, crate::Irqs);
+    let spi = PioSpi::new(
+        &mut pio.common,
+        pio.sm0,
+        cyw43_pio::DEFAULT_CLOCK_DIVIDER,
+        pio.irq0,
+        cs,
+        resources.clk,
+        resources.dat,
+        resources.dma,
+    );
+
+    let state = picoserve::make_static!(cyw43::State, cyw43::State::new());
+    // The cyw43439 shares its SPI bus between the WiFi and Bluetooth cores, so
+    // both `net_device` and the BLE HCI transport (`bt_device`) come back
+    // from the same `new` call and depend on `wifi_task` (spawned below) to
+    // keep pumping that shared bus; nothing on either core makes progress
+    // without it.
+    let (net_device, bt_device, mut control, runner) =
+        cyw43::new_with_bluetooth(state, pwr, spi, fw, btfw).await;
+    spawner.must_spawn(wifi_task(runner));
+
+    control.init(clm).await;
+    control.set_power_management(cyw43::PowerManagementMode::None).await;
+
+    let mut flash = Flash::<_, Async, { embassy_rp::flash::FLASH_BASE as usize }>::new(
+        resources.flash,
+        resources.flash_dma,
+    );
+
+    let (stack, runner) = embassy_net::new(
+        net_device,
+        config,
+        stack_resources,
+        embassy_rp::clocks::RoscRng.gen(),
+    );
+    spawner.must_spawn(net_task(runner));
+
+    let mut credentials = provisioning::load_credentials(&mut flash).await.unwrap_or(WifiCredentials {
+        ssid: heapless::String::try_from(core::option_env!("WIFI_NETWORK").unwrap_or("")).unwrap(),
+        password: heapless::String::try_from(core::option_env!("WIFI_PASSWORD").unwrap_or("")).unwrap(),
+    });
+
+    // `bt_device` is consumed by the BLE peripheral it backs, so we can only
+    // take one trip through provisioning per boot; a join that keeps failing
+    // after that falls back to retrying the last-known credentials forever.
+    let mut bt_device = Some(bt_device);
+
+    'join: loop {
+        let mut join_failures = 0;
+
+        while join_failures < MAX_JOIN_ATTEMPTS_BEFORE_PROVISIONING {
+            match control
+                .join(
+                    credentials.ssid.as_str(),
+                    JoinOptions::new(credentials.password.as_bytes()),
+                )
+                .await
+            {
+                Ok(_) => {
+                    defmt::info!("Connected to WiFi");
+                    break 'join;
+                }
+                Err(e) => {
+                    defmt::error!("Failed to connect to WiFi: STATUS = {:?}", e.status);
+                    join_failures += 1;
+                    embassy_time::Timer::after(Duration::from_secs(1)).await;
+                }
+            }
+        }
+
+        let Some(bt) = bt_device.take() else {
+            defmt::error!("Still can't join WiFi and BLE provisioning already ran this boot; retrying last credentials");
+            continue 'join;
+        };
+
+        defmt::warn!("Repeated join failures, starting BLE provisioning");
+        let bt_controller = bt_hci::controller::ExternalController::<_, 10>::new(bt);
+        let new_credentials = provisioning::provision_over_ble(bt_controller).await;
+        provisioning::store_credentials(&mut flash, &new_credentials).await;
+        credentials = new_credentials;
+    }
+
+    *CREDENTIALS.lock().await = Some(credentials);
+    WIFI_CONNECTED.store(1, core::sync::atomic::Ordering::Relaxed);
+
+    let control = picoserve::make_static!(
+        Mutex<CriticalSectionRawMutex, cyw43::Control<'static>>,
+        Mutex::new(control)
+    );
+    spawner.must_spawn(link_monitor_task(control, stack));
+
+    (stack, Control { inner: control })
+}
+
+/// Periodically samples RSSI into [`WIFI_RSSI_DBM`] and rejoins with the
+/// last-known-good credentials if the link drops — the one-shot
+/// `control.join` loop in [`init`] only ever runs once per boot, so without
+/// this a dropped association would be silent and fatal.
+///
+/// Association loss is read from `stack.is_link_up()` rather than from
+/// `rssi()`: RSSI is a signal-quality reading, not a join-status query, so a
+/// successful read doesn't mean the radio is still associated (and an
+/// infallible `rssi()` couldn't report disconnection via `Err` at all).
+#[embassy_executor::task]
+async fn link_monitor_task(
+    control: &'static Mutex<CriticalSectionRawMutex, cyw43::Control<'static>>,
+    stack: Stack<'static>,
+) -> ! {
+    use core::sync::atomic::Ordering;
+
+    loop {
+        embassy_time::Timer::after(LINK_POLL_INTERVAL).await;
+
+        WIFI_RSSI_DBM.store(control.lock().await.rssi().await as u32, Ordering::Relaxed);
+
+        if stack.is_link_up() {
+            WIFI_CONNECTED.store(1, Ordering::Relaxed);
+            continue;
+        }
+
+        defmt::warn!("WiFi link appears down, rejoining");
+        WIFI_CONNECTED.store(0, Ordering::Relaxed);
+
+        let Some(credentials) = CREDENTIALS.lock().await.clone() else {
+            continue;
+        };
+        // The lock is taken once for the rssi read above and again here for
+        // the join, not held across the `is_link_up` check in between;
+        // `set_status_led` (also guarded by `control`) waits while a join is
+        // in progress.
+        match control
+            .lock()
+            .await
+            .join(
+                credentials.ssid.as_str(),
+                JoinOptions::new(credentials.password.as_bytes()),
+            )
+            .await
+        {
+            Ok(_) => {
+                defmt::info!("Rejoined WiFi");
+                WIFI_CONNECTED.store(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                defmt::error!("Rejoin failed: STATUS = {:?}", e.status);
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! BLE-based WiFi provisioning.
+//!
+//! A "WiFi Provisioning" GATT service exposed over `trouble`, so credentials
+//! can change without a reflash. A phone connects and writes the SSID and
+//! passphrase characteristics; picow then tears the BLE peripheral down,
+//! persists them to flash, and attempts `control.join`. The compile-time
+//! `WIFI_NETWORK`/`WIFI_PASSWORD` values are used only while flash holds no
+//! credentials.
+
+use embassy_futures::select::{select, Either};
+use embassy_rp::flash::{Async, Flash};
+use embassy_rp::peripherals::FLASH;
+use trouble_host::prelude::*;
+
+/// Flash sector reserved for provisioning state, at the top of the 2MB
+/// Pico W flash (the bootloader and firmware image live below this offset).
+const CREDENTIAL_FLASH_OFFSET: u32 = 0x1F_F000;
+const CREDENTIAL_FLASH_SIZE: usize = 4096;
+const CREDENTIAL_MAGIC: u32 = 0x50_49_43_57; // "PICW"
+
+const MAX_SSID_LEN: usize = 32;
+const MAX_PASSWORD_LEN: usize = 64;
+
+/// RP2040 flash only accepts whole-page program operations (`WRITE_SIZE` on
+/// `embassy_rp::flash::Flash`), so [`store_credentials`] assembles the header
+/// and body into one page-sized buffer and issues a single write rather than
+/// two sub-page ones, which panic at runtime.
+const FLASH_WRITE_SIZE: usize = 256;
+
+#[derive(Clone)]
+pub struct WifiCredentials {
+    pub ssid: heapless::String<MAX_SSID_LEN>,
+    pub password: heapless::String<MAX_PASSWORD_LEN>,
+}
+
+/// Reads previously-provisioned credentials out of flash, if any have been
+/// written and the magic/length header is intact.
+pub async fn load_credentials(
+    flash: &mut Flash<'static, FLASH, Async, { embassy_rp::flash::FLASH_BASE as usize }>,
+) -> Option<WifiCredentials> {
+    let mut header = [0u8; 8];
+    flash
+        .read(CREDENTIAL_FLASH_OFFSET, &mut header)
+        .await
+        .ok()?;
+
+    let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    if magic != CREDENTIAL_MAGIC {
+        return None;
+    }
+
+    let ssid_len = header[4] as usize;
+    let password_len = header[5] as usize;
+    if ssid_len > MAX_SSID_LEN || password_len > MAX_PASSWORD_LEN {
+        return None;
+    }
+
+    let mut body = [0u8; MAX_SSID_LEN + MAX_PASSWORD_LEN];
+    flash
+        .read(CREDENTIAL_FLASH_OFFSET + 8, &mut body)
+        .await
+        .ok()?;
+
+    let ssid = core::str::from_utf8(&body[..ssid_len]).ok()?;
+    let password = core::str::from_utf8(&body[ssid_len..ssid_len + password_len]).ok()?;
+
+    Some(WifiCredentials {
+        ssid: heapless::String::try_from(ssid).ok()?,
+        password: heapless::String::try_from(password).ok()?,
+    })
+}
+
+/// Erases the credential sector and writes the new SSID/passphrase.
+pub async fn store_credentials(
+    flash: &mut Flash<'static, FLASH, Async, { embassy_rp::flash::FLASH_BASE as usize }>,
+    creds: &WifiCredentials,
+) {
+    flash
+        .erase(
+            CREDENTIAL_FLASH_OFFSET,
+            CREDENTIAL_FLASH_OFFSET + CREDENTIAL_FLASH_SIZE as u32,
+        )
+        .await
+        .unwrap();
+
+    let mut page = [0u8; FLASH_WRITE_SIZE];
+    page[0..4].copy_from_slice(&CREDENTIAL_MAGIC.to_le_bytes());
+    page[4] = creds.ssid.len() as u8;
+    page[5] = creds.password.len() as u8;
+    page[8..8 + creds.ssid.len()].copy_from_slice(creds.ssid.as_bytes());
+    page[8 + creds.ssid.len()..8 + creds.ssid.len() + creds.password.len()]
+        .copy_from_slice(creds.password.as_bytes());
+
+    flash.write(CREDENTIAL_FLASH_OFFSET, &page).await.unwrap();
+}
+
+/// GATT status values exposed on the status characteristic while provisioning.
+///
+/// There's no `JoinFailed` variant: `control.join` (in
+/// [`crate::net::wifi::init`] and its link monitor) only runs after this
+/// function has already returned and torn the BLE peripheral down, so a join
+/// failure has nowhere left to
+/// notify. Surfacing it would mean keeping the connection alive across the
+/// join attempt, which is a bigger restructuring than this status
+/// characteristic is worth.
+#[repr(u8)]
+pub enum ProvisioningStatus {
+    WaitingForCredentials = 0,
+    CredentialsReceived = 1,
+}
+
+#[gatt_service(uuid = "c9af0000-1fcb-4f55-9b6a-0050c25d3c01")]
+struct ProvisioningService {
+    #[characteristic(uuid = "c9af0001-1fcb-4f55-9b6a-0050c25d3c01", write)]
+    ssid: heapless::Vec<u8, MAX_SSID_LEN>,
+
+    #[characteristic(uuid = "c9af0002-1fcb-4f55-9b6a-0050c25d3c01", write)]
+    password: heapless::Vec<u8, MAX_PASSWORD_LEN>,
+
+    #[characteristic(uuid = "c9af0003-1fcb-4f55-9b6a-0050c25d3c01", read, notify)]
+    status: u8,
+}
+
+#[gatt_server]
+struct ProvisioningServer {
+    provisioning: ProvisioningService,
+}
+
+/// Runs the "WiFi Provisioning" BLE peripheral until both the SSID and
+/// password characteristics have been written, then tears down the BLE host
+/// and returns the credentials so the caller can attempt `control.join`.
+///
+/// `controller` is the shared cyw43/BLE radio transport; callers are
+/// responsible for making sure WiFi bring-up on the same radio is paused
+/// while this runs.
+pub async fn provision_over_ble<C: Controller>(controller: C) -> WifiCredentials {
+    let address = Address::random([0xC0, 0xFF, 0xEE, 0x50, 0x43, 0x57]);
+    let mut resources: HostResources<DefaultPacketPool, 1, 2, 27> = HostResources::new();
+    let stack = trouble_host::new(controller, &mut resources).set_random_address(address);
+    let Host {
+        mut peripheral,
+        runner,
+        ..
+    } = stack.build();
+
+    let server = ProvisioningServer::new_with_config(GapConfig::Peripheral(PeripheralConfig {
+        name: "picow-provision",
+        appearance: &appearance::UNKNOWN,
+    }))
+    .unwrap();
+
+    let advertisement = Advertisement::ConnectableScannableUndirected {
+        adv_data: &[],
+        scan_data: &[],
+    };
+
+    // `runner` drives the host's HCI event loop; nothing above (advertising,
+    // GATT reads/writes, notifications) makes progress unless it's polled
+    // alongside the provisioning logic, so race the two rather than
+    // discarding it.
+    let provisioning = async {
+        loop {
+            let conn = peripheral.advertise(&Default::default(), advertisement).await;
+            let Ok(conn) = conn else { continue };
+            let Ok(conn) = conn.with_attribute_server(&server) else {
+                continue;
+            };
+
+            let _ = server
+                .provisioning
+                .status
+                .notify(&server, &conn, &(ProvisioningStatus::WaitingForCredentials as u8))
+                .await;
+
+            let mut ssid: Option<heapless::String<MAX_SSID_LEN>> = None;
+            let mut password: Option<heapless::String<MAX_PASSWORD_LEN>> = None;
+
+            loop {
+                match conn.next().await {
+                    GattConnectionEvent::Disconnected { .. } => break,
+                    GattConnectionEvent::Gatt { event } => {
+                        if let GattEvent::Write(write) = &event {
+                            if write.handle() == server.provisioning.ssid.handle {
+                                if let Ok(value) = server.provisioning.ssid.get(&server) {
+                                    ssid = core::str::from_utf8(&value)
+                                        .ok()
+                                        .and_then(|s| heapless::String::try_from(s).ok());
+                                }
+                            } else if write.handle() == server.provisioning.password.handle {
+                                if let Ok(value) = server.provisioning.password.get(&server) {
+                                    password = core::str::from_utf8(&value)
+                                        .ok()
+                                        .and_then(|s| heapless::String::try_from(s).ok());
+                                }
+                            }
+                        }
+                        let _ = event.accept();
+                    }
+                    _ => {}
+                }
+
+                if let (Some(ssid), Some(password)) = (ssid.clone(), password.clone()) {
+                    let _ = server
+                        .provisioning
+                        .status
+                        .notify(&server, &conn, &(ProvisioningStatus::CredentialsReceived as u8))
+                        .await;
+                    return WifiCredentials { ssid, password };
+                }
+            }
+        }
+    };
+
+    match select(runner.run(), provisioning).await {
+        Either::First(_) => panic!("BLE host runner exited unexpectedly during provisioning"),
+        Either::Second(credentials) => credentials,
+    }
+}